@@ -0,0 +1,114 @@
+use crate::WtdError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BODY_EXTENSION: &str = "html";
+const META_EXTENSION: &str = "meta";
+const ETAG_KEY: &str = "etag";
+const LAST_MODIFIED_KEY: &str = "last-modified";
+
+/// A page body stored on disk along with the validators needed to revalidate it
+#[derive(Debug, PartialEq)]
+pub struct CachedPage {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Longest part of the url kept readable in a file name, well under the 255 bytes most file systems allow
+const MAX_KEY_PREFIX: usize = 100;
+
+/// Turns a url into a file name that is safe to use on disk.
+/// Alphanumerics, '-' and '.' are kept and everything else is hex encoded, cut short for long urls.
+/// A hash of the whole url is added so two urls never share a file
+fn cache_key(url: &str) -> String {
+    let mut prefix: String = url
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b == b'-' || b == b'.' {
+                (b as char).to_string()
+            } else {
+                format!("_{:02x}", b)
+            }
+        })
+        .collect();
+    prefix.truncate(MAX_KEY_PREFIX);
+    format!("{}-{:016x}", prefix, fnv1a_hash(url))
+}
+
+#[test]
+fn test_cache_key() {
+    assert_eq!(
+        cache_key("https://en.wikipedia.org/wiki/Member_states"),
+        format!(
+            "https_3a_2f_2fen.wikipedia.org_2fwiki_2fMember_5fstates-{:016x}",
+            fnv1a_hash("https://en.wikipedia.org/wiki/Member_states")
+        )
+    );
+    assert_ne!(cache_key("a/b"), cache_key("a_b"));
+    let long_url = format!("https://en.wikipedia.org/wiki/{}", "%C3%A9".repeat(100));
+    assert!(cache_key(&long_url).len() < 255);
+    assert_ne!(cache_key(&long_url), cache_key(&format!("{}x", long_url)));
+}
+
+/// 64 bit FNV-1a, it has to stay the same between builds so std's hasher won't do
+fn fnv1a_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn cache_path(cache_dir: &Path, url: &str, extension: &str) -> PathBuf {
+    cache_dir.join(format!("{}.{}", cache_key(url), extension))
+}
+
+/// Reads a page from the cache, returns None if the page has never been stored
+pub fn read(cache_dir: &Path, url: &str) -> Option<CachedPage> {
+    let body = fs::read_to_string(cache_path(cache_dir, url, BODY_EXTENSION)).ok()?;
+    let meta = fs::read_to_string(cache_path(cache_dir, url, META_EXTENSION)).unwrap_or_default();
+    let find_value = |key: &str| {
+        meta.lines()
+            .filter_map(|line| line.split_once(": "))
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| String::from(v))
+    };
+    Some(CachedPage {
+        etag: find_value(ETAG_KEY),
+        last_modified: find_value(LAST_MODIFIED_KEY),
+        body,
+    })
+}
+
+/// Stores a page and its validators in the cache directory, creating the directory if needed.
+/// A failure is reported as a warning, the caller can carry on without the cache
+pub fn write(cache_dir: &Path, url: &str, page: &CachedPage) -> Result<(), WtdError> {
+    let mut meta = String::new();
+    if let Some(etag) = &page.etag {
+        meta.push_str(&format!("{}: {}\n", ETAG_KEY, etag));
+    }
+    if let Some(last_modified) = &page.last_modified {
+        meta.push_str(&format!("{}: {}\n", LAST_MODIFIED_KEY, last_modified));
+    }
+    fs::create_dir_all(cache_dir)
+        .and_then(|()| fs::write(cache_path(cache_dir, url, BODY_EXTENSION), &page.body))
+        .and_then(|()| fs::write(cache_path(cache_dir, url, META_EXTENSION), meta))
+        .map_err(|err| {
+            eprintln!("Warning: Failed to write page to cache: {}", err);
+            WtdError::CacheWriteError
+        })
+}
+
+#[test]
+fn test_write_and_read() {
+    let cache_dir = std::env::temp_dir().join("wtd_test_write_and_read");
+    let url = "https://en.wikipedia.org/wiki/Member_states_of_the_United_Nations";
+    let page = CachedPage {
+        body: String::from("<h1>Member states of the United Nations</h1>"),
+        etag: Some(String::from("W/\"123\"")),
+        last_modified: Some(String::from("Wed, 21 Oct 2015 07:28:00 GMT")),
+    };
+    write(&cache_dir, url, &page).unwrap();
+    assert_eq!(read(&cache_dir, url), Some(page));
+    assert_eq!(read(&cache_dir, "https://example.com"), None);
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
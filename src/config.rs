@@ -0,0 +1,188 @@
+use crate::{SqlTypes, WtdError};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Cleaning rules read from the file given with --config, for example
+///
+/// ```toml
+/// [columns.Population]
+/// replace = [{ pattern = "\\(.*\\)", with = "" }]
+///
+/// [tables."List of sovereign states".columns.Code]
+/// type = "TEXT"
+/// trim = "*†"
+/// map = { "UK" = "United Kingdom" }
+/// ```
+///
+/// Rules under [columns] apply to a column of that name in any table, rules under a table replace them
+#[derive(Debug, Default)]
+pub struct Config {
+    columns: HashMap<String, ColumnRules>,
+    tables: HashMap<String, HashMap<String, ColumnRules>>,
+}
+
+/// The transforms applied to the text of every cell of a column, in the order they are listed
+#[derive(Debug)]
+pub struct ColumnRules {
+    replacements: Vec<(Regex, String)>,
+    /// Characters removed from both ends of the text, on top of whitespace
+    trim: Option<String>,
+    /// Whole values replaced by another, an empty one stands for NULL
+    map: HashMap<String, String>,
+    /// The type of the column, used instead of the one worked out from its values
+    pub column_type: Option<SqlTypes>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    columns: HashMap<String, ColumnRulesFile>,
+    #[serde(default)]
+    tables: HashMap<String, TableRulesFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TableRulesFile {
+    #[serde(default)]
+    columns: HashMap<String, ColumnRulesFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnRulesFile {
+    #[serde(default)]
+    replace: Vec<ReplacementFile>,
+    trim: Option<String>,
+    #[serde(default)]
+    map: HashMap<String, String>,
+    #[serde(rename = "type")]
+    column_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReplacementFile {
+    pattern: String,
+    with: String,
+}
+
+/// Reads the config file, complaining about anything in it that can't be used
+pub fn load(path: &Path) -> Result<Config, WtdError> {
+    let contents = std::fs::read_to_string(path).map_err(|err| {
+        eprintln!(
+            "Error: Could not read config file {}: {}",
+            path.display(),
+            err
+        );
+        WtdError::ConfigError
+    })?;
+    parse(&contents)
+}
+
+/// Reads the rules from the contents of a config file
+pub fn parse(contents: &str) -> Result<Config, WtdError> {
+    let file: ConfigFile = toml::from_str(contents).map_err(|err| {
+        eprintln!("Error: Invalid config file: {}", err);
+        WtdError::ConfigError
+    })?;
+    let columns = to_column_rules(file.columns)?;
+    let mut tables = HashMap::new();
+    for (table, table_rules) in file.tables {
+        tables.insert(table, to_column_rules(table_rules.columns)?);
+    }
+    Ok(Config { columns, tables })
+}
+
+fn to_column_rules(
+    columns: HashMap<String, ColumnRulesFile>,
+) -> Result<HashMap<String, ColumnRules>, WtdError> {
+    let mut column_rules = HashMap::new();
+    for (column, rules) in columns {
+        let mut replacements = Vec::new();
+        for replacement in rules.replace {
+            let pattern = Regex::new(&replacement.pattern).map_err(|err| {
+                eprintln!("Error: Invalid pattern for column {}: {}", column, err);
+                WtdError::ConfigError
+            })?;
+            replacements.push((pattern, replacement.with));
+        }
+        let column_type = match rules.column_type {
+            Some(column_type) => Some(column_type.parse().map_err(|err| {
+                eprintln!("Error: Invalid type for column {}: {}", column, err);
+                WtdError::ConfigError
+            })?),
+            None => None,
+        };
+        column_rules.insert(
+            column,
+            ColumnRules {
+                replacements,
+                trim: rules.trim,
+                map: rules.map,
+                column_type,
+            },
+        );
+    }
+    Ok(column_rules)
+}
+
+impl Config {
+    /// The rules for a column of a table, the table's own rules coming before the ones for any table
+    pub fn column_rules(&self, table: &str, column: &str) -> Option<&ColumnRules> {
+        self.tables
+            .get(table)
+            .and_then(|columns| columns.get(column))
+            .or_else(|| self.columns.get(column))
+    }
+}
+
+impl ColumnRules {
+    /// Runs the replacements, trimming and mapping over the text of a cell
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = String::from(text);
+        for (pattern, with) in &self.replacements {
+            text = pattern.replace_all(&text, with.as_str()).into_owned();
+        }
+        let trim_chars = self.trim.as_deref().unwrap_or_default();
+        let trimmed = text.trim_matches(|c: char| c.is_whitespace() || trim_chars.contains(c));
+        match self.map.get(trimmed) {
+            Some(mapped) => mapped.clone(),
+            None => String::from(trimmed),
+        }
+    }
+}
+
+#[test]
+fn test_config() {
+    let config = parse(
+        r#"
+        [columns.Population]
+        replace = [{ pattern = "\\s*\\(.*\\)", with = "" }]
+
+        [tables."List of sovereign states".columns.Code]
+        type = "text"
+        trim = "*†"
+        map = { "UK" = "United Kingdom", "—" = "" }
+        "#,
+    )
+    .unwrap();
+    let population = config.column_rules("Any table", "Population").unwrap();
+    assert_eq!(population.apply("1,234 (2019 estimate)"), "1,234");
+    assert_eq!(population.column_type, None);
+
+    let code = config
+        .column_rules("List of sovereign states", "Code")
+        .unwrap();
+    assert_eq!(code.apply(" 007*† "), "007");
+    assert_eq!(code.apply("UK†"), "United Kingdom");
+    assert_eq!(code.column_type, Some(SqlTypes::TEXT));
+    assert!(config.column_rules("Another table", "Code").is_none());
+
+    assert!(parse("[columns.Code]\ntype = \"DATE\"").is_err());
+    assert!(parse("[columns.Code]\nreplace = [{ pattern = \"(\", with = \"\" }]").is_err());
+    assert!(parse("[columns.Code]\nlowercase = true").is_err());
+}
@@ -0,0 +1,303 @@
+use crate::identifiers::{quote_identifier, table_identifier};
+use crate::infobox;
+use crate::metadata::Metadata;
+use crate::{
+    create_table, fetch_page, get_raw_table_rows, get_table, get_table_header_names, insert_rows,
+    number_rows, Command, WtdError, ROW_ID_COLUMN,
+};
+use reqwest::Url;
+use scraper::{Html, Selector};
+use std::time::Duration;
+
+const PARENT_ROWID_COLUMN: &str = "parent_rowid";
+/// Pause between requests so a crawl doesn't hammer the server
+const CRAWL_DELAY: Duration = Duration::from_millis(200);
+
+/// Follows the link in `column` for every row of the page's table and stores what is found on
+/// each linked page in `<table_name>_<column>`, with a parent_rowid column pointing back at the
+/// row_id of the row the link came from.
+/// By default the first table of each page is stored, with --crawl-infobox it's the infobox
+pub async fn crawl_linked_pages(
+    body: &str,
+    table_name: &str,
+    column: &str,
+    database_name: &str,
+    args: &Command,
+) -> Result<(), WtdError> {
+    let links = get_row_links(body, column, args)?;
+    let limit = args.crawl_limit.unwrap_or(links.len());
+    let pages = fetch_linked_pages(links.into_iter().take(limit), args).await;
+    let child_table_name = format!("{}_{}", table_name, column);
+    if args.crawl_infobox {
+        infobox::store_wide_infoboxes(
+            &child_table_name,
+            pages,
+            parent_rowid_column(table_name, args),
+            database_name,
+            args,
+        )
+    } else {
+        store_linked_tables(table_name, &child_table_name, pages, database_name, args)
+    }
+}
+
+/// The parent_rowid column of the rows split or crawled from the rows of `table_name`,
+/// a foreign key to its row_id
+pub fn parent_rowid_column(table_name: &str, args: &Command) -> (String, String) {
+    (
+        String::from(PARENT_ROWID_COLUMN),
+        format!(
+            "INTEGER REFERENCES {}({})",
+            quote_identifier(&table_identifier(table_name, &args.identifiers)),
+            ROW_ID_COLUMN
+        ),
+    )
+}
+
+/// Downloads every link, returning the parent rowid, url and body of each page.
+/// Pages that can't be fetched are skipped with a warning
+async fn fetch_linked_pages(
+    links: impl Iterator<Item = (i64, Option<String>)>,
+    args: &Command,
+) -> Vec<(i64, String, String)> {
+    let mut pages = Vec::new();
+    for (parent_rowid, url) in links {
+        let url = match url {
+            Some(url) => url,
+            None => continue,
+        };
+        if !args.offline {
+            tokio::time::delay_for(CRAWL_DELAY).await;
+        }
+        println!("Crawling {}", url);
+        match fetch_page(&url, args).await {
+            Ok(page) => pages.push((parent_rowid, url, page)),
+            Err(err) => eprintln!("Warning: Skipping {}: {}", url, err),
+        }
+    }
+    pages
+}
+
+/// Stores the first table of every crawled page in a single table, with the metadata of the first
+fn store_linked_tables(
+    parent_table_name: &str,
+    table_name: &str,
+    pages: Vec<(i64, String, String)>,
+    database_name: &str,
+    args: &Command,
+) -> Result<(), WtdError> {
+    let mut child_headers: Option<Vec<(String, String)>> = None;
+    let mut child_metadata: Vec<Metadata> = Vec::new();
+    let mut child_rows: Vec<Vec<String>> = Vec::new();
+
+    for (parent_rowid, url, page) in pages {
+        let table = match get_table(&page, args) {
+            Ok(table) => table,
+            Err(err) => {
+                eprintln!("Warning: Skipping {}: {}", url, err);
+                continue;
+            }
+        };
+        // Every linked page goes into the same table so they all need the same columns
+        match &child_headers {
+            Some(expected) if !same_column_names(expected, &table.columns) => {
+                eprintln!(
+                    "Warning: Skipping {}: its table columns don't match the first crawled table",
+                    url
+                );
+                continue;
+            }
+            Some(_) => {}
+            None => {
+                child_headers = Some(table.columns);
+                child_metadata = table.metadata;
+            }
+        }
+        for cleaned_row in table.rows {
+            if !cleaned_row.is_empty() {
+                let mut child_row = vec![parent_rowid.to_string()];
+                child_row.extend(cleaned_row);
+                child_rows.push(child_row);
+            }
+        }
+    }
+
+    match child_headers {
+        Some(headers) => {
+            let mut child_columns = vec![parent_rowid_column(parent_table_name, args)];
+            child_columns.extend(headers);
+            create_table(
+                table_name,
+                child_columns.clone(),
+                child_metadata,
+                database_name,
+                args,
+            )?;
+            if child_rows.is_empty() {
+                return Ok(());
+            }
+            insert_rows(table_name, &child_columns, child_rows, database_name, args)
+        }
+        None => {
+            eprintln!("Warning: None of the linked pages contained a table");
+            Ok(())
+        }
+    }
+}
+
+fn same_column_names(a: &[(String, String)], b: &[(String, String)]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.0 == y.0)
+}
+
+/// Pairs the row_id each row will get when inserted with the absolute url of the link in `column`
+fn get_row_links(
+    body: &str,
+    column: &str,
+    args: &Command,
+) -> Result<Vec<(i64, Option<String>)>, WtdError> {
+    let headers = get_table_header_names(body, args)?;
+    let column_index = headers
+        .iter()
+        .position(|h| h == column)
+        .ok_or(WtdError::CrawlColumnNotFound)?;
+    Ok(number_rows(get_raw_table_rows(body, args)?)
+        .map(|(row_id, row)| {
+            let link = row
+                .get(column_index)
+                .and_then(|cell| get_cell_link(cell, &args.url));
+            (row_id, link)
+        })
+        .collect())
+}
+
+#[test]
+fn test_get_row_links() {
+    let html = std::fs::read_to_string("fixtures/memberStatesTable.html").unwrap();
+    let args = crate::test_args(&[]);
+    assert_eq!(
+        get_row_links(&html, "Member state", &args).unwrap(),
+        vec![(
+            1,
+            Some(String::from("https://en.wikipedia.org/wiki/Afghanistan"))
+        )]
+    );
+    match get_row_links(&html, "Capital", &args) {
+        Err(WtdError::CrawlColumnNotFound) => {}
+        other => panic!("Expected CrawlColumnNotFound, got {:?}", other),
+    }
+}
+
+/// Returns the absolute url of the first link in a cell, ignoring in-page links like citations
+pub fn get_cell_link(cell: &str, page_url: &str) -> Option<String> {
+    let fragment = Html::parse_fragment(cell);
+    let link_selector = Selector::parse("a[href]").unwrap();
+    let href = fragment
+        .select(&link_selector)
+        .filter_map(|a| a.value().attr("href"))
+        .find(|href| !href.starts_with('#'))?;
+    Url::parse(page_url)
+        .and_then(|base| base.join(href))
+        .ok()
+        .map(String::from)
+}
+
+#[test]
+fn test_get_cell_link() {
+    let page_url = "https://en.wikipedia.org/wiki/List_of_countries_and_dependencies_by_population";
+    let cell = r###"<a href="/wiki/Demographics_of_China" title="Demographics of China">China</a><sup id="cite_ref-4" class="reference"><a href="#cite_note-4">[b]</a></sup>"###;
+    assert_eq!(
+        get_cell_link(cell, page_url),
+        Some(String::from(
+            "https://en.wikipedia.org/wiki/Demographics_of_China"
+        ))
+    );
+
+    let citation_only = r###"1,000<sup class="reference"><a href="#cite_note-4">[b]</a></sup>"###;
+    assert_eq!(get_cell_link(citation_only, page_url), None);
+}
+
+#[tokio::test]
+async fn test_crawl_linked_pages() {
+    let (test_dir, database_name) = crate::test_database("wtd_test_crawl_linked_pages", "test.db");
+    let cache_dir = test_dir.join("cache");
+    let parent_url = "https://en.wikipedia.org/wiki/Member_states_of_the_United_Nations";
+    let linked_page = crate::cache::CachedPage {
+        body: std::fs::read_to_string("fixtures/tableHeaders.html").unwrap(),
+        etag: None,
+        last_modified: None,
+    };
+    crate::cache::write(
+        &cache_dir,
+        "https://en.wikipedia.org/wiki/Afghanistan",
+        &linked_page,
+    )
+    .unwrap();
+    let args = <Command as structopt::StructOpt>::from_iter(&[
+        "wtd",
+        parent_url,
+        "--offline",
+        "--cache-dir",
+        cache_dir.to_str().unwrap(),
+        "--crawl",
+        "Member state",
+    ]);
+
+    let parent = std::fs::read_to_string("fixtures/memberStatesTable.html").unwrap();
+    crawl_linked_pages(
+        &parent,
+        "Member states",
+        "Member state",
+        database_name.to_str().unwrap(),
+        &args,
+    )
+    .await
+    .unwrap();
+
+    let connection = sqlite::open(&database_name).unwrap();
+    let mut statement = connection
+        .prepare("SELECT parent_rowid, Rank, Population FROM Member_states_Member_state;")
+        .unwrap();
+    assert_eq!(statement.next().unwrap(), sqlite::State::Row);
+    assert_eq!(statement.read::<i64>(0).unwrap(), 1);
+    assert_eq!(statement.read::<i64>(1).unwrap(), 1);
+    assert_eq!(statement.read::<i64>(2).unwrap(), 1402944920);
+    let mut statement = connection
+        .prepare("SELECT value FROM _wtd_metadata WHERE column_name = 'Population' AND key = 'original_name';")
+        .unwrap();
+    assert_eq!(statement.next().unwrap(), sqlite::State::Row);
+
+    // With --strict the rows of a linked page that don't fit its columns are rejected
+    let strict_args = <Command as structopt::StructOpt>::from_iter(&[
+        "wtd",
+        parent_url,
+        "--offline",
+        "--cache-dir",
+        cache_dir.to_str().unwrap(),
+        "--crawl",
+        "Member state",
+        "--strict",
+        "--type",
+        "Date=INTEGER",
+    ]);
+    let strict_database_name = test_dir.join("strict.db");
+    crawl_linked_pages(
+        &parent,
+        "Member states",
+        "Member state",
+        strict_database_name.to_str().unwrap(),
+        &strict_args,
+    )
+    .await
+    .unwrap();
+    let connection = sqlite::open(&strict_database_name).unwrap();
+    let mut statement = connection
+        .prepare("SELECT table_name, row_rowid FROM _wtd_rejects;")
+        .unwrap();
+    assert_eq!(statement.next().unwrap(), sqlite::State::Row);
+    assert_eq!(
+        statement.read::<String>(0).unwrap(),
+        "Member_states_Member_state"
+    );
+    assert_eq!(statement.read::<i64>(1).unwrap(), 1);
+    std::fs::remove_dir_all(&test_dir).unwrap();
+}
@@ -0,0 +1,116 @@
+use crate::{
+    create_insert_statement_from_rows, prepare_table, sink_table, Command, ScrapedTable, WtdError,
+};
+use duckdb::Connection;
+
+/// Creates a table from the page in the DuckDB file at `path` and fills it, returning the name of
+/// the table
+pub fn extract_data(body: &str, path: &str, args: &Command) -> Result<String, WtdError> {
+    let prepared = prepare_table(body, args)?;
+    let connection = Connection::open(path).map_err(|err| {
+        eprintln!("Error: Could not open DuckDB file {}, {}", path, err);
+        WtdError::DuckDbError
+    })?;
+    let table_name = prepared.table_name;
+    store_table(&connection, &table_name, prepared.table, args)?;
+    execute(&connection, &prepared.key_statement)?;
+    if let Some(footer) = prepared.footer {
+        store_table(&connection, &format!("{}_footer", table_name), footer, args)?;
+    }
+    Ok(table_name)
+}
+
+/// The DuckDB type for a sqlite type. NUMERIC would be a DECIMAL with three decimal places,
+/// so it's a DOUBLE
+fn duckdb_type(sql_type: &str) -> &'static str {
+    match sql_type {
+        "INTEGER" => "BIGINT",
+        "REAL" | "NUMERIC" => "DOUBLE",
+        _ => "VARCHAR",
+    }
+}
+
+#[test]
+fn test_duckdb_type() {
+    assert_eq!(duckdb_type("INTEGER"), "BIGINT");
+    assert_eq!(duckdb_type("NUMERIC"), "DOUBLE");
+    assert_eq!(duckdb_type("TEXT"), "VARCHAR");
+}
+
+/// Creates the table with its metadata, then inserts the rows
+fn store_table(
+    connection: &Connection,
+    table_name: &str,
+    table: ScrapedTable,
+    args: &Command,
+) -> Result<(), WtdError> {
+    let table = sink_table(table_name, table, args, duckdb_type);
+    execute(connection, &table.create_statement)?;
+    println!("Successfully Created table");
+
+    // DuckDB has no rowid to keep, --row-id is the way to keep the order of the rows
+    if table.rows.iter().all(|row| row.is_empty()) {
+        return Ok(());
+    }
+    println!("Inserting rows");
+    execute(
+        connection,
+        &create_insert_statement_from_rows(table_name, table.rows, args),
+    )
+}
+
+/// Runs a batch of statements against the file
+fn execute(connection: &Connection, statement: &str) -> Result<(), WtdError> {
+    if statement.is_empty() {
+        return Ok(());
+    }
+    connection.execute_batch(statement).map_err(|err| {
+        eprintln!("Error: {}\nSQL Statement: {}", err, statement);
+        WtdError::DuckDbError
+    })
+}
+
+#[test]
+fn test_duckdb_extract_data() {
+    let (test_dir, path) = crate::test_database("wtd_test_duckdb_extract_data", "test.duckdb");
+    let path = path.to_str().unwrap();
+    let html = std::fs::read_to_string("fixtures/percentages.html").unwrap();
+    let args = crate::test_args(&["--duckdb", path, "--row-id", "--unique-key", "Country"]);
+
+    assert_eq!(
+        extract_data(&html, path, &args).unwrap(),
+        "Population shares"
+    );
+
+    let connection = Connection::open(path).unwrap();
+    let mut statement = connection
+        .prepare("SELECT row_id, \"Country\", \"% of world population\" FROM \"Population_shares\" ORDER BY row_id")
+        .unwrap();
+    let rows: Vec<(i64, String, Option<f64>)> = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1], (2, String::from("India"), Some(17.7)));
+    assert_eq!(rows[2].2, None);
+    // The file is only opened by one connection at a time
+    drop(statement);
+    drop(connection);
+
+    // The table already exists
+    assert!(matches!(
+        extract_data(&html, path, &args),
+        Err(WtdError::DuckDbError)
+    ));
+    let args = crate::test_args(&["--duckdb", path, "--if-exists", "replace"]);
+    extract_data(&html, path, &args).unwrap();
+    let count: i64 = Connection::open(path)
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM \"Population_shares\"", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(count, 3);
+    std::fs::remove_dir_all(&test_dir).unwrap();
+}
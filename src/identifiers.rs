@@ -0,0 +1,196 @@
+use deunicode::deunicode;
+use std::str::FromStr;
+
+/// Keywords that sqlite won't accept as an unquoted identifier
+/// https://www.sqlite.org/lang_keywords.html
+#[rustfmt::skip]
+const SQLITE_KEYWORDS: &[&str] = &[
+    "ABORT", "ACTION", "ADD", "AFTER", "ALL", "ALTER", "ALWAYS", "ANALYZE", "AND", "AS", "ASC",
+    "ATTACH", "AUTOINCREMENT", "BEFORE", "BEGIN", "BETWEEN", "BY", "CASCADE", "CASE", "CAST",
+    "CHECK", "COLLATE", "COLUMN", "COMMIT", "CONFLICT", "CONSTRAINT", "CREATE", "CROSS", "CURRENT",
+    "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP", "DATABASE", "DEFAULT", "DEFERRABLE",
+    "DEFERRED", "DELETE", "DESC", "DETACH", "DISTINCT", "DO", "DROP", "EACH", "ELSE", "END",
+    "ESCAPE", "EXCEPT", "EXCLUDE", "EXCLUSIVE", "EXISTS", "EXPLAIN", "FAIL", "FILTER", "FIRST",
+    "FOLLOWING", "FOR", "FOREIGN", "FROM", "FULL", "GENERATED", "GLOB", "GROUP", "GROUPS",
+    "HAVING", "IF", "IGNORE", "IMMEDIATE", "IN", "INDEX", "INDEXED", "INITIALLY", "INNER",
+    "INSERT", "INSTEAD", "INTERSECT", "INTO", "IS", "ISNULL", "JOIN", "KEY", "LAST", "LEFT",
+    "LIKE", "LIMIT", "MATCH", "MATERIALIZED", "NATURAL", "NO", "NOT", "NOTHING", "NOTNULL", "NULL",
+    "NULLS", "OF", "OFFSET", "ON", "OR", "ORDER", "OTHERS", "OUTER", "OVER", "PARTITION", "PLAN",
+    "PRAGMA", "PRECEDING", "PRIMARY", "QUERY", "RAISE", "RANGE", "RECURSIVE", "REFERENCES",
+    "REGEXP", "REINDEX", "RELEASE", "RENAME", "REPLACE", "RESTRICT", "RETURNING", "RIGHT",
+    "ROLLBACK", "ROW", "ROWS", "SAVEPOINT", "SELECT", "SET", "TABLE", "TEMP", "TEMPORARY", "THEN",
+    "TIES", "TO", "TRANSACTION", "TRIGGER", "UNBOUNDED", "UNION", "UNIQUE", "UPDATE", "USING",
+    "VACUUM", "VALUES", "VIEW", "VIRTUAL", "WHEN", "WHERE", "WINDOW", "WITH", "WITHOUT",
+];
+
+/// How scraped names are turned into table and column names
+#[derive(Debug, PartialEq)]
+pub enum IdentifierPolicy {
+    /// Names are used as scraped, only spaces in table names become underscores
+    Raw,
+    /// Names are transliterated to ASCII letters, digits and underscores, keeping their case
+    Ascii,
+    /// Like Ascii but lower case
+    SnakeCase,
+}
+
+impl FromStr for IdentifierPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(IdentifierPolicy::Raw),
+            "ascii" => Ok(IdentifierPolicy::Ascii),
+            "snake-case" => Ok(IdentifierPolicy::SnakeCase),
+            _ => Err(format!(
+                "Unknown identifier policy {}, expected raw, ascii or snake-case",
+                s
+            )),
+        }
+    }
+}
+
+/// Quotes an identifier for use in an sql statement
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", str::replace(identifier, "\"", "\"\""))
+}
+
+#[test]
+fn test_quote_identifier() {
+    assert_eq!(quote_identifier("Population"), "\"Population\"");
+    assert_eq!(
+        quote_identifier("The \"Big\" one"),
+        "\"The \"\"Big\"\" one\""
+    );
+}
+
+/// Returns the table name to use for a scraped title
+pub fn table_identifier(name: &str, policy: &IdentifierPolicy) -> String {
+    match policy {
+        IdentifierPolicy::Raw => str::replace(name, " ", "_"),
+        _ => sanitize(name, policy),
+    }
+}
+
+#[test]
+fn test_table_identifier() {
+    let title = "List of countries and dependencies by population";
+    assert_eq!(
+        table_identifier(title, &IdentifierPolicy::Raw),
+        "List_of_countries_and_dependencies_by_population"
+    );
+    assert_eq!(
+        table_identifier("Côte d'Ivoire", &IdentifierPolicy::Ascii),
+        "Cote_d_Ivoire"
+    );
+    assert_eq!(
+        table_identifier("Côte d'Ivoire", &IdentifierPolicy::SnakeCase),
+        "cote_d_ivoire"
+    );
+}
+
+/// Returns the column names to use for scraped headers.
+/// Names that collide once the policy is applied get a _2, _3, ... suffix
+pub fn column_identifiers(headers: &[String], policy: &IdentifierPolicy) -> Vec<String> {
+    make_unique(
+        headers
+            .iter()
+            .map(|header| match policy {
+                IdentifierPolicy::Raw => header.clone(),
+                _ => sanitize(header, policy),
+            })
+            .collect(),
+    )
+}
+
+/// Gives names that collide, ignoring case as sqlite does, a _2, _3, ... suffix
+pub fn make_unique(names: Vec<String>) -> Vec<String> {
+    let mut unique_names: Vec<String> = Vec::new();
+    for name in names {
+        let taken = |candidate: &str| {
+            unique_names
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(candidate))
+        };
+        let mut unique = name.clone();
+        let mut suffix = 2;
+        while taken(&unique) {
+            unique = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        unique_names.push(unique);
+    }
+    unique_names
+}
+
+#[test]
+fn test_column_identifiers() {
+    let headers: Vec<String> = vec![
+        String::from("Rank"),
+        String::from("Country (or dependent territory)"),
+        String::from("% of world population"),
+        String::from("Area (km²)"),
+        String::from("Area km2"),
+        String::from("Order"),
+        String::from("2020"),
+    ];
+    assert_eq!(
+        column_identifiers(&headers, &IdentifierPolicy::SnakeCase),
+        vec![
+            "rank",
+            "country_or_dependent_territory",
+            "percent_of_world_population",
+            "area_km2",
+            "area_km2_2",
+            "order_",
+            "_2020",
+        ]
+    );
+    assert_eq!(
+        column_identifiers(&headers, &IdentifierPolicy::Ascii)[1],
+        "Country_or_dependent_territory"
+    );
+    assert_eq!(
+        column_identifiers(&headers, &IdentifierPolicy::Raw),
+        headers
+    );
+
+    let duplicates = vec![String::from("Name"), String::from("name")];
+    assert_eq!(
+        column_identifiers(&duplicates, &IdentifierPolicy::Raw),
+        vec!["Name", "name_2"]
+    );
+}
+
+/// Transliterates to ASCII and replaces anything that isn't a letter, digit or underscore.
+/// Keywords get a trailing underscore and names starting with a digit a leading one
+/// so the result never needs quoting
+fn sanitize(name: &str, policy: &IdentifierPolicy) -> String {
+    let spelled_out = name
+        .replace('%', " percent ")
+        .replace('&', " and ")
+        .replace('#', " number ");
+    let ascii = deunicode(&spelled_out);
+    let cased = match policy {
+        IdentifierPolicy::SnakeCase => ascii.to_lowercase(),
+        _ => ascii,
+    };
+    let words: Vec<&str> = cased
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let joined = words.join("_");
+    if joined.is_empty() {
+        return String::from("_");
+    }
+    if joined.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("_{}", joined);
+    }
+    if SQLITE_KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(&joined))
+    {
+        return format!("{}_", joined);
+    }
+    joined
+}
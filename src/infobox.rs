@@ -0,0 +1,268 @@
+use crate::crawl::get_cell_link;
+use crate::{
+    clean_header_string, clean_row, create_insert_statement_from_rows, create_table, derive_type,
+    execute_insert, get_page_title_from_html, quote, Command, WtdError,
+};
+use scraper::{Html, Selector};
+
+const WIKI_INFOBOX_ELEMENT: &str = "table.infobox";
+/// Infoboxes mark sub rows such as Area • Total with a bullet
+const INFOBOX_SUB_ROW_MARKER: char = '•';
+
+/// A labelled row of an infobox
+#[derive(Debug, PartialEq)]
+pub struct InfoboxEntry {
+    pub key: String,
+    /// The raw html of the value cell, cleaned with clean_row when inserted
+    pub value: String,
+    /// Absolute url of the first link in the value cell
+    pub link: Option<String>,
+}
+
+/// Returns the labelled rows of the first infobox on the page.
+/// Sub rows like "• Total" are prefixed with the row or header above them so keys stay unique
+pub fn get_infobox_entries(body: &str, page_url: &str) -> Result<Vec<InfoboxEntry>, WtdError> {
+    let fragment = Html::parse_fragment(body);
+    let infobox_selector = Selector::parse(WIKI_INFOBOX_ELEMENT).unwrap();
+    let row_selector = Selector::parse("tr").unwrap();
+    let label_selector = Selector::parse("th").unwrap();
+    let data_selector = Selector::parse("td").unwrap();
+
+    let infobox = match fragment.select(&infobox_selector).next() {
+        Some(infobox) => infobox,
+        None => return Err(WtdError::InfoboxNotFound),
+    };
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    for row in infobox.select(&row_selector) {
+        let label = match row.select(&label_selector).next() {
+            Some(label) => clean_header_string(label.inner_html()),
+            None => continue, // Images and captions
+        };
+        match row.select(&data_selector).next() {
+            Some(data) => {
+                let key = match label.strip_prefix(INFOBOX_SUB_ROW_MARKER) {
+                    Some(sub_label) if !section.is_empty() => {
+                        format!("{} {}", section, sub_label.trim())
+                    }
+                    _ => {
+                        section = label.clone();
+                        label
+                    }
+                };
+                entries.push(InfoboxEntry {
+                    key,
+                    value: data.inner_html(),
+                    link: get_cell_link(&data.inner_html(), page_url),
+                });
+            }
+            None => section = label, // Header rows that span the whole infobox
+        }
+    }
+    Ok(entries)
+}
+
+#[test]
+fn test_get_infobox_entries() {
+    let html = std::fs::read_to_string("fixtures/infobox.html").unwrap();
+    let entries = get_infobox_entries(&html, "https://en.wikipedia.org/wiki/Afghanistan").unwrap();
+    let keys: Vec<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+    assert_eq!(
+        keys,
+        vec![
+            "Capital",
+            "Official languages",
+            "Area Total",
+            "Population",
+            "Population Total",
+            "Calling code"
+        ]
+    );
+    assert_eq!(
+        entries[0].link,
+        Some(String::from("https://en.wikipedia.org/wiki/Kabul"))
+    );
+    assert_eq!(entries[4].value, "32,890,171");
+
+    let no_infobox = std::fs::read_to_string("fixtures/tableHeaders.html").unwrap();
+    match get_infobox_entries(&no_infobox, "https://example.com") {
+        Err(WtdError::InfoboxNotFound) => {}
+        other => panic!("Expected InfoboxNotFound, got {:?}", other),
+    }
+}
+
+/// Cleans a single infobox value into an sql literal
+fn clean_infobox_value(value: &str, args: &Command) -> String {
+    clean_row(vec![String::from(value)], args).remove(0)
+}
+
+/// Returns the cleaned title of the page, falling back on the url when there is no title
+fn get_page_name(body: &str, page_url: &str) -> String {
+    get_page_title_from_html(body)
+        .first()
+        .map(|title| clean_header_string(String::from(title)))
+        .unwrap_or_else(|| String::from(page_url))
+}
+
+/// Stores the page's infobox as a (page, key, value, link) table named <title>_infobox,
+/// returning the name of the table
+pub fn extract_infobox(
+    body: &str,
+    database_name: &str,
+    args: &Command,
+) -> Result<String, WtdError> {
+    let entries = get_infobox_entries(body, &args.url)?;
+    let page = get_page_name(body, &args.url);
+    let table_name = format!("{}_infobox", page);
+    let columns = ["page", "key", "value", "link"]
+        .iter()
+        .map(|column| (String::from(*column), String::from("TEXT")))
+        .collect();
+    create_table(&table_name, columns, vec![], database_name, args)?;
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            vec![
+                quote(&page),
+                quote(&entry.key),
+                clean_infobox_value(&entry.value, args),
+                entry
+                    .link
+                    .as_deref()
+                    .map(quote)
+                    .unwrap_or_else(|| String::from("NULL")),
+            ]
+        })
+        .collect();
+    if !rows.is_empty() {
+        execute_insert(
+            &create_insert_statement_from_rows(&table_name, rows, args),
+            database_name,
+        )?;
+    }
+    Ok(table_name)
+}
+
+/// Stores the infoboxes of crawled pages with one wide row per page.
+/// Every key seen on any page becomes a column, pages without that key get a NULL
+pub fn store_wide_infoboxes(
+    table_name: &str,
+    pages: Vec<(i64, String, String)>,
+    parent_rowid_column: (String, String),
+    database_name: &str,
+    args: &Command,
+) -> Result<(), WtdError> {
+    let mut columns: Vec<(String, String)> = Vec::new();
+    let mut page_entries = Vec::new();
+    for (parent_rowid, url, body) in pages {
+        match get_infobox_entries(&body, &url) {
+            Ok(entries) => {
+                for entry in &entries {
+                    if !columns.iter().any(|(key, _)| *key == entry.key) {
+                        columns.push((
+                            entry.key.clone(),
+                            derive_type(&entry.value, args).to_string(),
+                        ));
+                    }
+                }
+                page_entries.push((parent_rowid, get_page_name(&body, &url), entries));
+            }
+            Err(err) => eprintln!("Warning: Skipping {}: {}", url, err),
+        }
+    }
+    if page_entries.is_empty() {
+        eprintln!("Warning: None of the linked pages contained an infobox");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = page_entries
+        .iter()
+        .map(|(parent_rowid, page, entries)| {
+            let mut row = vec![parent_rowid.to_string(), quote(page)];
+            row.extend(columns.iter().map(|(key, _)| {
+                // Only the first value is kept if a page repeats a key
+                match entries.iter().find(|entry| entry.key == *key) {
+                    Some(entry) => clean_infobox_value(&entry.value, args),
+                    None => String::from("NULL"),
+                }
+            }));
+            row
+        })
+        .collect();
+    let mut table_columns = vec![
+        parent_rowid_column,
+        (String::from("page"), String::from("TEXT")),
+    ];
+    table_columns.extend(columns);
+    create_table(table_name, table_columns, vec![], database_name, args)?;
+    execute_insert(
+        &create_insert_statement_from_rows(table_name, rows, args),
+        database_name,
+    )
+}
+
+#[test]
+fn test_extract_infobox() {
+    let (test_dir, database_name) = crate::test_database("wtd_test_extract_infobox", "test.db");
+    let html = std::fs::read_to_string("fixtures/infobox.html").unwrap();
+
+    let table_name = extract_infobox(
+        &html,
+        database_name.to_str().unwrap(),
+        &crate::test_args(&[]),
+    )
+    .unwrap();
+    assert_eq!(table_name, "Afghanistan_infobox");
+
+    let connection = sqlite::open(&database_name).unwrap();
+    let mut statement = connection
+        .prepare("SELECT page, value, link FROM Afghanistan_infobox WHERE key = 'Capital';")
+        .unwrap();
+    assert_eq!(statement.next().unwrap(), sqlite::State::Row);
+    assert_eq!(statement.read::<String>(0).unwrap(), "Afghanistan");
+    assert_eq!(statement.read::<String>(1).unwrap(), "Kabul 33°N 65°E");
+    assert_eq!(
+        statement.read::<String>(2).unwrap(),
+        "https://en.wikipedia.org/wiki/Kabul"
+    );
+    std::fs::remove_dir_all(&test_dir).unwrap();
+}
+
+#[test]
+fn test_store_wide_infoboxes() {
+    let (test_dir, database_name) =
+        crate::test_database("wtd_test_store_wide_infoboxes", "test.db");
+    let pages = vec![
+        (
+            1,
+            String::from("https://en.wikipedia.org/wiki/Afghanistan"),
+            std::fs::read_to_string("fixtures/infobox.html").unwrap(),
+        ),
+        (
+            2,
+            String::from("https://en.wikipedia.org/wiki/List_of_countries"),
+            std::fs::read_to_string("fixtures/tableHeaders.html").unwrap(),
+        ),
+    ];
+
+    let args = crate::test_args(&[]);
+    store_wide_infoboxes(
+        "Member states_Member state",
+        pages,
+        crate::crawl::parent_rowid_column("Member states", &args),
+        database_name.to_str().unwrap(),
+        &args,
+    )
+    .unwrap();
+
+    let connection = sqlite::open(&database_name).unwrap();
+    let mut statement = connection
+        .prepare("SELECT parent_rowid, page, \"Population Total\" FROM Member_states_Member_state;")
+        .unwrap();
+    assert_eq!(statement.next().unwrap(), sqlite::State::Row);
+    assert_eq!(statement.read::<i64>(0).unwrap(), 1);
+    assert_eq!(statement.read::<String>(1).unwrap(), "Afghanistan");
+    assert_eq!(statement.read::<i64>(2).unwrap(), 32890171);
+    assert_eq!(statement.next().unwrap(), sqlite::State::Done); // The page without an infobox is skipped
+    std::fs::remove_dir_all(&test_dir).unwrap();
+}
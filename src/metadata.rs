@@ -0,0 +1,91 @@
+use crate::quote;
+
+/// Table holding everything we know about the imported tables and columns that doesn't fit in the
+/// schema itself, one (table_name, column_name, key, value) row per fact.
+/// column_name is NULL for facts about the whole table
+pub const METADATA_TABLE: &str = "_wtd_metadata";
+/// The name of the table or column as it appeared on the page
+pub const ORIGINAL_NAME_KEY: &str = "original_name";
+/// Set on the column whose cells are row headers, the natural key of the table
+pub const ROW_HEADER_KEY: &str = "row_header";
+/// The unit the values of a column are in
+pub const UNIT_KEY: &str = "unit";
+/// The unit a column was in on the page before it was converted to SI units
+pub const ORIGINAL_UNIT_KEY: &str = "original_unit";
+/// Set on percentage columns, fraction when 18% is stored as 0.18 and percent when it's stored as 18
+pub const PERCENT_KEY: &str = "percent";
+
+/// A single fact about an imported table or one of its columns
+pub struct Metadata {
+    pub column_name: Option<String>,
+    pub key: &'static str,
+    pub value: String,
+}
+
+impl Metadata {
+    pub fn table(key: &'static str, value: &str) -> Metadata {
+        Metadata {
+            column_name: None,
+            key,
+            value: String::from(value),
+        }
+    }
+
+    pub fn column(column_name: &str, key: &'static str, value: &str) -> Metadata {
+        Metadata {
+            column_name: Some(String::from(column_name)),
+            key,
+            value: String::from(value),
+        }
+    }
+}
+
+/// Creates the statements that store the metadata of a table, creating the metadata table if needed
+/// and removing what was stored about an earlier table of the same name
+pub fn create_metadata_statement(table_name: &str, metadata: &[Metadata]) -> String {
+    let create_statement = format!(
+        "CREATE TABLE IF NOT EXISTS {0} (table_name TEXT, column_name TEXT, key TEXT, value TEXT); \
+         DELETE FROM {0} WHERE table_name = {1};",
+        METADATA_TABLE,
+        quote(table_name)
+    );
+    if metadata.is_empty() {
+        return create_statement;
+    }
+    let values: Vec<String> = metadata
+        .iter()
+        .map(|m| {
+            format!(
+                "({}, {}, {}, {})",
+                quote(table_name),
+                m.column_name
+                    .as_deref()
+                    .map(quote)
+                    .unwrap_or_else(|| String::from("NULL")),
+                quote(m.key),
+                quote(&m.value)
+            )
+        })
+        .collect();
+    format!(
+        "{} INSERT INTO {} VALUES {};",
+        create_statement,
+        METADATA_TABLE,
+        values.join(", ")
+    )
+}
+
+#[test]
+fn test_create_metadata_statement() {
+    let metadata = vec![
+        Metadata::table(ORIGINAL_NAME_KEY, "Member states"),
+        Metadata::column("member_state", ORIGINAL_NAME_KEY, "Member state"),
+    ];
+    assert_eq!(
+        create_metadata_statement("member_states", &metadata),
+        "CREATE TABLE IF NOT EXISTS _wtd_metadata (table_name TEXT, column_name TEXT, key TEXT, value TEXT); \
+         DELETE FROM _wtd_metadata WHERE table_name = 'member_states'; \
+         INSERT INTO _wtd_metadata VALUES ('member_states', NULL, 'original_name', 'Member states'), \
+         ('member_states', 'member_state', 'original_name', 'Member state');"
+    );
+}
@@ -0,0 +1,283 @@
+/// Marks put in front of a number to say it's an estimate
+const APPROXIMATION_MARKS: &[&str] = &["approx.", "ca.", "c.", "~", "≈"];
+/// Currency symbols and codes, the longer ones first so US$ isn't mistaken for $
+const CURRENCY_SYMBOLS: &[&str] = &[
+    "US$", "NZ$", "HK$", "A$", "C$", "R$", "$", "€", "£", "¥", "₹", "₩", "₽", "₺", "₴", "₦", "฿",
+];
+/// Words scaling the number in front of them, with the power of ten they stand for
+const MAGNITUDE_WORDS: &[(&str, i32)] = &[
+    ("thousand", 3),
+    ("million", 6),
+    ("mn", 6),
+    ("billion", 9),
+    ("bn", 9),
+    ("trillion", 12),
+];
+/// Spaces used to separate groups of thousands
+const SPACE_SEPARATORS: &[char] = &[' ', '\u{a0}', '\u{2009}', '\u{202f}'];
+/// Separators between the two ends of a range. A hyphen without spaces is also used in phone
+/// numbers and the like, so it only separates ranges in columns that say they hold them
+const RANGE_SEPARATORS: &[&str] = &["–", "—", " to ", " - "];
+const HYPHEN: &str = "-";
+/// Words in a header saying the column holds ranges
+const RANGE_HEADER_WORDS: &[&str] = &["range", "ranges", "span", "min", "max"];
+/// The most the two ends of a range can differ in digits, further apart they are more likely
+/// two numbers that happen to be written next to each other
+const MAX_RANGE_DIGIT_GAP: usize = 3;
+
+/// A number found in a cell, normalized to something sqlite can read such as 1200000000 or -5.5
+#[derive(Debug, PartialEq)]
+pub enum ParsedNumber {
+    Single(String),
+    /// The lowest and highest number of something like 10–20
+    Range(String, String),
+}
+
+/// Parses numbers written the way they are on wikipedia, with thousands separators, currency
+/// symbols, magnitude words like million, unicode minus signs and ranges.
+/// With decimal_comma set , is the decimal separator and . separates thousands
+pub fn parse_number(text: &str, decimal_comma: bool) -> Option<ParsedNumber> {
+    match parse_single_number(text, decimal_comma) {
+        Some(number) => Some(ParsedNumber::Single(number)),
+        None => parse_range(text, RANGE_SEPARATORS, decimal_comma),
+    }
+}
+
+/// Like parse_number, also reading a hyphen without spaces as in 10-20 as a range.
+/// For columns whose header or unit says they hold ranges
+pub fn parse_number_or_hyphen_range(text: &str, decimal_comma: bool) -> Option<ParsedNumber> {
+    parse_number(text, decimal_comma).or_else(|| parse_range(text, &[HYPHEN], decimal_comma))
+}
+
+/// Whether a header such as "Depth range" or "Min–max" says the column holds ranges
+pub fn header_suggests_range(header: &str) -> bool {
+    header
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| RANGE_HEADER_WORDS.contains(&word))
+}
+
+#[test]
+fn test_parse_number() {
+    let single = |s: &str| Some(ParsedNumber::Single(String::from(s)));
+    assert_eq!(parse_number("1,402,843,280", false), single("1402843280"));
+    assert_eq!(parse_number("18.0%", false), single("18.0"));
+    assert_eq!(parse_number("−5", false), single("-5"));
+    assert_eq!(parse_number("~300", false), single("300"));
+    assert_eq!(parse_number("$1.2 billion", false), single("1200000000"));
+    assert_eq!(parse_number("US$ 350 million", false), single("350000000"));
+    assert_eq!(parse_number("€12.50", false), single("12.50"));
+    assert_eq!(
+        parse_number("1\u{2009}234\u{2009}567", false),
+        single("1234567")
+    );
+    assert_eq!(parse_number("1 234,5", true), single("1234.5"));
+    assert_eq!(parse_number("1.234,5", true), single("1234.5"));
+    assert_eq!(
+        parse_number("10–20", false),
+        Some(ParsedNumber::Range(String::from("10"), String::from("20")))
+    );
+    assert_eq!(
+        parse_number("−10 to −5.5", false),
+        Some(ParsedNumber::Range(
+            String::from("-10"),
+            String::from("-5.5")
+        ))
+    );
+    assert_eq!(parse_number("12 34", false), None);
+    assert_eq!(parse_number("Marshall Islands", false), None);
+    assert_eq!(parse_number("1 Jul 2018", false), None);
+    assert_eq!(parse_number("2020-05-28", false), None);
+    assert_eq!(parse_number("007", false), None);
+    assert_eq!(parse_number("01–05", false), None);
+    // Phone numbers and seasons aren't ranges
+    assert_eq!(parse_number("555-1234", false), None);
+    assert_eq!(parse_number("2019–20", false), None);
+    assert_eq!(parse_number("1–20000", false), None);
+    assert_eq!(
+        parse_number("10 - 20", false),
+        Some(ParsedNumber::Range(String::from("10"), String::from("20")))
+    );
+    assert_eq!(parse_number("10-20", false), None);
+    assert_eq!(
+        parse_number_or_hyphen_range("10-20", false),
+        Some(ParsedNumber::Range(String::from("10"), String::from("20")))
+    );
+    assert_eq!(parse_number_or_hyphen_range("-5", false), single("-5"));
+    assert_eq!(parse_number("0.5", false), single("0.5"));
+}
+
+/// Codes that are written with digits but aren't numbers, such as 007 or 0044 whose leading zeros
+/// would be lost, and calling codes such as +1 242 that would otherwise read as 1242
+pub fn is_code(text: &str) -> bool {
+    let text = text.trim();
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let leading_zero = text.len() > 1 && text.starts_with('0') && all_digits(text);
+    let calling_code = match text.strip_prefix('+') {
+        Some(rest) => {
+            rest.starts_with(|c: char| c.is_ascii_digit())
+                && rest.contains(SPACE_SEPARATORS)
+                && rest
+                    .split(SPACE_SEPARATORS)
+                    .all(|group| !group.is_empty() && all_digits(group))
+        }
+        None => false,
+    };
+    leading_zero || calling_code
+}
+
+#[test]
+fn test_is_code() {
+    assert!(is_code("007"));
+    assert!(is_code(" 0044 "));
+    assert!(is_code("+1 242"));
+    assert!(!is_code("0"));
+    assert!(!is_code("0.5"));
+    assert!(!is_code("+12"));
+    assert!(!is_code("+1,234"));
+    assert!(!is_code("1 234"));
+}
+
+#[test]
+fn test_header_suggests_range() {
+    assert!(header_suggests_range("Depth range (m)"));
+    assert!(header_suggests_range("Min–max"));
+    assert!(!header_suggests_range("Phone"));
+    assert!(!header_suggests_range("Minister"));
+}
+
+/// Reads the two ends of a range, which must go from low to high and be about as long as each other
+fn parse_range(text: &str, separators: &[&str], decimal_comma: bool) -> Option<ParsedNumber> {
+    separators.iter().find_map(|separator| {
+        // Skipping the first character so a leading minus sign isn't taken for a separator
+        let start = text.chars().next()?.len_utf8();
+        let split = text[start..].find(separator)? + start;
+        let low = parse_single_number(&text[..split], decimal_comma)?;
+        let high = parse_single_number(&text[split + separator.len()..], decimal_comma)?;
+        let whole_digits = |number: &str| {
+            let unsigned = number.trim_start_matches('-');
+            unsigned.split('.').next().unwrap_or_default().len()
+        };
+        let in_order = low.parse::<f64>().ok()? <= high.parse::<f64>().ok()?;
+        if !in_order || whole_digits(&low).abs_diff(whole_digits(&high)) > MAX_RANGE_DIGIT_GAP {
+            return None;
+        }
+        Some(ParsedNumber::Range(low, high))
+    })
+}
+
+fn parse_single_number(text: &str, decimal_comma: bool) -> Option<String> {
+    if is_code(text) {
+        return None;
+    }
+    let mut s = text.trim().replace('\u{2212}', "-");
+    for mark in APPROXIMATION_MARKS {
+        if let Some(rest) = s.strip_prefix(mark) {
+            s = String::from(rest.trim_start());
+        }
+    }
+    s = String::from(s.trim_end_matches('%').trim_end());
+
+    let mut exponent: i32 = 0;
+    if let Some((number, word)) = s.rsplit_once(|c: char| SPACE_SEPARATORS.contains(&c)) {
+        if let Some((_, power)) = MAGNITUDE_WORDS
+            .iter()
+            .find(|(magnitude, _)| magnitude.eq_ignore_ascii_case(word))
+        {
+            exponent = *power;
+            s = String::from(number.trim_end());
+        }
+    }
+    for symbol in CURRENCY_SYMBOLS {
+        if let Some(rest) = s.strip_prefix(symbol) {
+            s = String::from(rest.trim_start());
+            break;
+        }
+        if let Some(rest) = s.strip_suffix(symbol) {
+            s = String::from(rest.trim_end());
+            break;
+        }
+    }
+
+    let (thousands_separator, decimal_separator) = if decimal_comma {
+        ('.', ',')
+    } else {
+        (',', '.')
+    };
+    // Spaces only count as separators when they split the number into groups of three digits
+    if s.contains(SPACE_SEPARATORS) && !spaces_separate_thousands(&s, decimal_separator) {
+        return None;
+    }
+    let number: String = s
+        .chars()
+        .filter(|c| *c != thousands_separator && !SPACE_SEPARATORS.contains(c))
+        .map(|c| if c == decimal_separator { '.' } else { c })
+        .collect();
+    if !is_plain_number(&number) {
+        return None;
+    }
+    Some(scale(&number, exponent))
+}
+
+fn spaces_separate_thousands(s: &str, decimal_separator: char) -> bool {
+    let whole = s.split(decimal_separator).next().unwrap_or_default();
+    let whole = whole.trim_start_matches(['-', '+']);
+    let mut groups = whole.split(SPACE_SEPARATORS);
+    let first = groups.next().unwrap_or_default();
+    (1..=3).contains(&first.len())
+        && groups.all(|group| group.len() == 3 && group.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Digits with an optional sign and decimal part
+pub fn is_plain_number(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (whole, fraction) = match unsigned.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (unsigned, None),
+    };
+    !whole.is_empty()
+        && whole.chars().all(|c| c.is_ascii_digit())
+        && fraction.is_none_or(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Multiplies a number by a power of ten by moving the decimal point, so 1.2 billion is exactly
+/// 1200000000 and 18.3% exactly 0.183 rather than whatever floating point makes of them
+pub fn scale(number: &str, exponent: i32) -> String {
+    if exponent == 0 {
+        return String::from(number);
+    }
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number.trim_start_matches('+')),
+    };
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let digits = format!("{}{}", whole, fraction);
+    // Where the decimal point ends up, counted in digits from the left
+    let point = whole.len() as i32 + exponent;
+    let scaled = if point >= digits.len() as i32 {
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else if point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let (before, after) = digits.split_at(point as usize);
+        format!("{}.{}", before, after)
+    };
+    let trimmed = scaled.trim_start_matches('0');
+    if trimmed.is_empty() || trimmed.starts_with('.') {
+        format!("{}0{}", sign, trimmed)
+    } else {
+        format!("{}{}", sign, trimmed)
+    }
+}
+
+#[test]
+fn test_scale() {
+    assert_eq!(scale("1.2", 9), "1200000000");
+    assert_eq!(scale("0.5", 3), "500");
+    assert_eq!(scale("1.23456", 3), "1234.56");
+    assert_eq!(scale("-2", 6), "-2000000");
+    assert_eq!(scale("18.0", 0), "18.0");
+    assert_eq!(scale("18.3", -2), "0.183");
+    assert_eq!(scale("0.000712", -2), "0.00000712");
+    assert_eq!(scale("-250", -2), "-2.50");
+}
@@ -0,0 +1,152 @@
+use crate::identifiers::quote_identifier;
+use crate::{prepare_table, sink_table, Command, ScrapedTable, WtdError};
+use postgres::{Client, NoTls};
+use std::io::Write;
+
+/// Creates a table from the page in the postgres database at `url` and fills it with COPY,
+/// returning the name of the table
+pub fn extract_data(body: &str, url: &str, args: &Command) -> Result<String, WtdError> {
+    let prepared = prepare_table(body, args)?;
+    let mut client = Client::connect(url, NoTls).map_err(|err| {
+        eprintln!("Error: Could not connect to postgres database, {}", err);
+        WtdError::PostgresError
+    })?;
+    let table_name = prepared.table_name;
+    store_table(&mut client, &table_name, prepared.table, args)?;
+    execute(&mut client, &prepared.key_statement)?;
+    if let Some(footer) = prepared.footer {
+        store_table(&mut client, &format!("{}_footer", table_name), footer, args)?;
+    }
+    Ok(table_name)
+}
+
+/// The postgres type for a sqlite type, BIGINT as sqlite integers are 64 bits
+fn postgres_type(sql_type: &str) -> &'static str {
+    match sql_type {
+        "INTEGER" => "BIGINT",
+        "REAL" => "DOUBLE PRECISION",
+        "NUMERIC" => "NUMERIC",
+        _ => "TEXT",
+    }
+}
+
+#[test]
+fn test_postgres_type() {
+    assert_eq!(postgres_type("INTEGER"), "BIGINT");
+    assert_eq!(postgres_type("REAL"), "DOUBLE PRECISION");
+    assert_eq!(postgres_type("TEXT"), "TEXT");
+}
+
+/// Turns a cleaned value, a sql literal, into a field of COPY's text format
+fn copy_value(value: &str) -> String {
+    if value == "NULL" {
+        return String::from("\\N");
+    }
+    let text = match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        Some(text) => text.replace("''", "'"),
+        None => String::from(value),
+    };
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[test]
+fn test_copy_value() {
+    assert_eq!(copy_value("NULL"), "\\N");
+    assert_eq!(copy_value("-5.5"), "-5.5");
+    assert_eq!(copy_value("'Côte d''Ivoire'"), "Côte d'Ivoire");
+    assert_eq!(copy_value("'first\nsecond\\'"), "first\\nsecond\\\\");
+}
+
+/// Creates the table with its metadata, then loads the rows with COPY
+fn store_table(
+    client: &mut Client,
+    table_name: &str,
+    table: ScrapedTable,
+    args: &Command,
+) -> Result<(), WtdError> {
+    let table = sink_table(table_name, table, args, postgres_type);
+    execute(client, &table.create_statement)?;
+    println!("Successfully Created table");
+
+    let quoted_columns: Vec<String> = table
+        .column_identifiers
+        .iter()
+        .map(|column_name| quote_identifier(column_name))
+        .collect();
+    let copy_statement = format!(
+        "COPY {} ({}) FROM STDIN",
+        quote_identifier(&table.identifier),
+        quoted_columns.join(", ")
+    );
+    println!("Inserting rows");
+    match copy_rows(client, &copy_statement, &table.rows) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            eprintln!("Error: Failed to copy rows into table: {}", err);
+            Err(WtdError::PostgresError)
+        }
+    }
+}
+
+fn copy_rows(
+    client: &mut Client,
+    copy_statement: &str,
+    rows: &[Vec<String>],
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut writer = client.copy_in(copy_statement)?;
+    for row in rows.iter().filter(|row| !row.is_empty()) {
+        let fields: Vec<String> = row.iter().map(|value| copy_value(value)).collect();
+        writeln!(writer, "{}", fields.join("\t"))?;
+    }
+    Ok(writer.finish()?)
+}
+
+/// Runs a batch of statements on the server
+fn execute(client: &mut Client, statement: &str) -> Result<(), WtdError> {
+    if statement.is_empty() {
+        return Ok(());
+    }
+    client.batch_execute(statement).map_err(|err| {
+        eprintln!("Error: {}\nSQL Statement: {}", err, statement);
+        WtdError::PostgresError
+    })
+}
+
+/// Needs a database, run it with WTD_TEST_POSTGRES_URL set to one such as
+/// postgres://postgres@localhost/postgres and cargo test --features postgres -- --ignored
+#[test]
+#[ignore]
+fn test_postgres_extract_data() {
+    let url = std::env::var("WTD_TEST_POSTGRES_URL").expect("WTD_TEST_POSTGRES_URL isn't set");
+    let mut client = Client::connect(&url, NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS \"Population_shares\"; DROP TABLE IF EXISTS _wtd_metadata;",
+        )
+        .unwrap();
+    let html = std::fs::read_to_string("fixtures/percentages.html").unwrap();
+    let args = crate::test_args(&["--postgres", &url, "--row-id"]);
+
+    assert_eq!(
+        extract_data(&html, &url, &args).unwrap(),
+        "Population shares"
+    );
+
+    let rows = client
+        .query(
+            "SELECT row_id, \"Country\", \"% of world population\" FROM \"Population_shares\" ORDER BY row_id",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1].get::<_, i64>(0), 2);
+    assert_eq!(rows[1].get::<_, String>(1), "India");
+    assert_eq!(rows[1].get::<_, f64>(2), 17.7);
+    assert_eq!(rows[2].get::<_, Option<f64>>(2), None);
+    client
+        .batch_execute("DROP TABLE \"Population_shares\"; DROP TABLE _wtd_metadata;")
+        .unwrap();
+}
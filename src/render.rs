@@ -0,0 +1,190 @@
+use crate::{
+    add_row_id_column, check_type_overrides, get_page_title_from_html, get_table, has_row_id,
+    remove_html_tags, Command, OutputFormat, ScrapedTable, SqlTypes, WtdError,
+};
+
+/// Renders the table of the page as markdown or wikitext, numbers aligned to the right
+pub fn render(body: &str, args: &Command) -> Result<String, WtdError> {
+    check_type_overrides(body, args)?;
+    let mut table = get_table(body, args)?;
+    if has_row_id(args) {
+        add_row_id_column(&mut table);
+    }
+    let title = get_page_title_from_html(body)
+        .first()
+        .map(|title| remove_html_tags(title))
+        .unwrap_or_default();
+    Ok(match args.format {
+        OutputFormat::Wikitext => wikitext(&title, &table),
+        _ => markdown(&table),
+    })
+}
+
+/// The text of a cleaned value, a sql literal, with nothing for NULL
+fn display_value(value: &str) -> String {
+    if value == "NULL" {
+        return String::new();
+    }
+    match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        Some(text) => text.replace("''", "'"),
+        None => String::from(value),
+    }
+}
+
+/// Numbers are aligned to the right so their digits line up
+fn is_numeric(column_type: &str) -> bool {
+    let sql_type = column_type.split(' ').next().unwrap_or_default();
+    matches!(
+        sql_type.parse(),
+        Ok(SqlTypes::INTEGER) | Ok(SqlTypes::REAL) | Ok(SqlTypes::NUMERIC)
+    )
+}
+
+/// The header and cells of every column, ready to be laid out
+fn table_text(
+    table: &ScrapedTable,
+    escape: impl Fn(String) -> String,
+) -> (Vec<String>, Vec<Vec<String>>) {
+    let headers = table
+        .columns
+        .iter()
+        .map(|(name, _)| escape(name.clone()))
+        .collect();
+    let rows = table
+        .rows
+        .iter()
+        .filter(|row| !row.is_empty())
+        .map(|row| {
+            let mut cells: Vec<String> = row
+                .iter()
+                .map(|value| escape(display_value(value)))
+                .collect();
+            // Short rows are filled out so every line has the same columns
+            cells.resize(table.columns.len(), String::new());
+            cells
+        })
+        .collect();
+    (headers, rows)
+}
+
+/// A markdown table with the columns padded to the same width
+fn markdown(table: &ScrapedTable) -> String {
+    let (headers, rows) = table_text(table, |text| text.replace('|', "\\|").replace('\n', "<br>"));
+    let numeric: Vec<bool> = table
+        .columns
+        .iter()
+        .map(|(_, column_type)| is_numeric(column_type))
+        .collect();
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(vec![header.chars().count(), 3])
+                .max()
+                .unwrap_or(3)
+        })
+        .collect();
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut text = line(
+        headers
+            .iter()
+            .zip(&widths)
+            .map(|(header, width)| format!("{:<1$}", header, width))
+            .collect(),
+    );
+    text.push_str(&line(
+        widths
+            .iter()
+            .zip(&numeric)
+            .map(|(width, numeric)| {
+                if *numeric {
+                    format!("{}:", "-".repeat(width - 1))
+                } else {
+                    "-".repeat(*width)
+                }
+            })
+            .collect(),
+    ));
+    for row in rows {
+        text.push_str(&line(
+            row.iter()
+                .zip(widths.iter().zip(&numeric))
+                .map(|(cell, (width, numeric))| {
+                    if *numeric {
+                        format!("{:>1$}", cell, width)
+                    } else {
+                        format!("{:<1$}", cell, width)
+                    }
+                })
+                .collect(),
+        ));
+    }
+    text
+}
+
+#[test]
+fn test_markdown() {
+    let html = std::fs::read_to_string("fixtures/percentages.html").unwrap();
+    let args = crate::test_args(&["--format", "markdown"]);
+    assert_eq!(
+        render(&html, &args).unwrap(),
+        "\
+| Country      | % of world population | Yearly change |
+| ------------ | --------------------: | ------------: |
+| China        |                 18.47 |          0.39 |
+| India        |                 17.70 |          -1.2 |
+| Vatican City |                       |               |
+"
+    );
+}
+
+/// A wikitable, captioned with the page title
+fn wikitext(title: &str, table: &ScrapedTable) -> String {
+    let (headers, rows) = table_text(table, |text| {
+        text.replace('|', "&#124;").replace('\n', "<br />")
+    });
+    let mut text = String::from("{| class=\"wikitable sortable\"\n");
+    if !title.is_empty() {
+        text.push_str(&format!("|+ {}\n", title));
+    }
+    text.push_str(&format!("! {}\n", headers.join(" !! ")));
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&table.columns)
+            .map(|(cell, (_, column_type))| {
+                if is_numeric(column_type) {
+                    format!("style=\"text-align:right\" | {}", cell)
+                } else {
+                    cell.clone()
+                }
+            })
+            .collect();
+        text.push_str(&format!("|-\n| {}\n", cells.join(" || ").trim_end()));
+    }
+    text.push_str("|}\n");
+    text
+}
+
+#[test]
+fn test_wikitext() {
+    let html = std::fs::read_to_string("fixtures/percentages.html").unwrap();
+    let args = crate::test_args(&["--format", "wikitext"]);
+    assert_eq!(
+        render(&html, &args).unwrap(),
+        "\
+{| class=\"wikitable sortable\"
+|+ Population shares
+! Country !! % of world population !! Yearly change
+|-
+| China || style=\"text-align:right\" | 18.47 || style=\"text-align:right\" | 0.39
+|-
+| India || style=\"text-align:right\" | 17.70 || style=\"text-align:right\" | -1.2
+|-
+| Vatican City || style=\"text-align:right\" |  || style=\"text-align:right\" |
+|}
+"
+    );
+}
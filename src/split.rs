@@ -0,0 +1,177 @@
+use crate::crawl::parent_rowid_column;
+use crate::{
+    clean_value, create_table, get_raw_table_rows, get_table_header_names, get_text, insert_rows,
+    is_null, number_rows, Command, LineBreaks, WtdError,
+};
+
+/// Splits the values of a cell on line breaks, list items, semicolons and commas followed by a space.
+/// Commas without a space are left alone as they are more likely to be in a number like 1,234
+fn split_cell(html: &str, args: &Command) -> Vec<String> {
+    get_text(html, &LineBreaks::Newline)
+        .split(['\n', ';'])
+        .flat_map(|part| part.split(", "))
+        .map(str::trim)
+        .filter(|value| !is_null(value, args))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn test_split_cell() {
+    let args = crate::test_args(&[]);
+    assert_eq!(
+        split_cell("<a href=\"/wiki/Dari\">Dari</a><br>Pashto", &args),
+        vec!["Dari", "Pashto"]
+    );
+    assert_eq!(
+        split_cell("<ul><li>English</li><li>French; Arabic</li></ul>", &args),
+        vec!["English", "French", "Arabic"]
+    );
+    assert_eq!(
+        split_cell("English, French, 1,234", &args),
+        vec!["English", "French", "1,234"]
+    );
+    assert!(split_cell("—", &args).is_empty());
+}
+
+/// Stores the values of a multi-valued column one per row in <table_name>_<column>, as
+/// (parent_rowid, position, value) with position counting from 1 within each cell.
+/// parent_rowid is a foreign key to the row_id of the parent table
+pub fn store_multi_valued_column(
+    body: &str,
+    table_name: &str,
+    column: &str,
+    database_name: &str,
+    args: &Command,
+) -> Result<(), WtdError> {
+    let headers = get_table_header_names(body, args)?;
+    let column_index = headers
+        .iter()
+        .position(|h| h == column)
+        .ok_or(WtdError::SplitColumnNotFound)?;
+    let mut child_rows = Vec::new();
+    for (parent_rowid, row) in number_rows(get_raw_table_rows(body, args)?) {
+        let values = row
+            .get(column_index)
+            .map(|cell| split_cell(cell, args))
+            .unwrap_or_default();
+        for (position, value) in values.iter().enumerate() {
+            child_rows.push(vec![
+                parent_rowid.to_string(),
+                (position + 1).to_string(),
+                clean_value(value, args),
+            ]);
+        }
+    }
+
+    let child_table_name = format!("{}_{}", table_name, column);
+    let columns = vec![
+        parent_rowid_column(table_name, args),
+        (String::from("position"), String::from("INTEGER")),
+        (String::from("value"), String::from("TEXT")),
+    ];
+    create_table(
+        &child_table_name,
+        columns.clone(),
+        vec![],
+        database_name,
+        args,
+    )?;
+    if child_rows.is_empty() {
+        return Ok(());
+    }
+    insert_rows(&child_table_name, &columns, child_rows, database_name, args)
+}
+
+#[test]
+fn test_store_multi_valued_column() {
+    let (test_dir, database_name) =
+        crate::test_database("wtd_test_store_multi_valued_column", "test.db");
+    let html = std::fs::read_to_string("fixtures/multiValued.html").unwrap();
+    let args = crate::test_args(&["--split-column", "Official languages"]);
+
+    store_multi_valued_column(
+        &html,
+        "Languages",
+        "Official languages",
+        database_name.to_str().unwrap(),
+        &args,
+    )
+    .unwrap();
+
+    let connection = sqlite::open(&database_name).unwrap();
+    let mut statement = connection
+        .prepare("SELECT parent_rowid, position, value FROM Languages_Official_languages;")
+        .unwrap();
+    let mut values = Vec::new();
+    while let sqlite::State::Row = statement.next().unwrap() {
+        values.push((
+            statement.read::<i64>(0).unwrap(),
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+        ));
+    }
+    assert_eq!(
+        values,
+        vec![
+            (1, 1, String::from("Dari")),
+            (1, 2, String::from("Pashto")),
+            (2, 1, String::from("Albanian")),
+            (3, 1, String::from("English")),
+            (3, 2, String::from("French")),
+        ]
+    );
+    match store_multi_valued_column(
+        &html,
+        "Languages",
+        "Capital",
+        database_name.to_str().unwrap(),
+        &args,
+    ) {
+        Err(WtdError::SplitColumnNotFound) => {}
+        other => panic!("Expected SplitColumnNotFound, got {:?}", other),
+    }
+    std::fs::remove_dir_all(&test_dir).unwrap();
+}
+
+#[test]
+fn test_split_column_foreign_key() {
+    let (test_dir, database_name) =
+        crate::test_database("wtd_test_split_column_foreign_key", "test.db");
+    let html = std::fs::read_to_string("fixtures/multiValued.html").unwrap();
+    let args = crate::test_args(&["--split-column", "Official languages"]);
+
+    crate::extract_data(&html, database_name.to_str().unwrap(), &args).unwrap();
+
+    let connection = sqlite::open(&database_name).unwrap();
+    connection.execute("VACUUM;").unwrap();
+    let mut statement = connection
+        .prepare("SELECT sql FROM sqlite_master WHERE name = 'Languages_Official_languages';")
+        .unwrap();
+    assert_eq!(statement.next().unwrap(), sqlite::State::Row);
+    assert!(statement
+        .read::<String>(0)
+        .unwrap()
+        .contains("\"parent_rowid\" INTEGER REFERENCES \"Languages\"(row_id)"));
+    let mut statement = connection
+        .prepare(
+            "SELECT Country, value FROM Languages JOIN Languages_Official_languages \
+             ON parent_rowid = row_id WHERE position = 2 ORDER BY row_id;",
+        )
+        .unwrap();
+    let mut values = Vec::new();
+    while let sqlite::State::Row = statement.next().unwrap() {
+        values.push((
+            statement.read::<String>(0).unwrap(),
+            statement.read::<String>(1).unwrap(),
+        ));
+    }
+    assert_eq!(
+        values,
+        vec![
+            (String::from("Afghanistan"), String::from("Pashto")),
+            (String::from("Cameroon"), String::from("French")),
+        ]
+    );
+    std::fs::remove_dir_all(&test_dir).unwrap();
+}
@@ -0,0 +1,210 @@
+use crate::identifiers::quote_identifier;
+use crate::numeric::is_plain_number;
+use crate::{number_rows, quote, SqlTypes};
+
+/// Table holding the rows --strict kept out of the imported tables, one
+/// (table_name, row_rowid, reason, row) row per rejected row. row_rowid is the rowid the row
+/// would have had, so rows split or crawled from it can still be matched up with it.
+/// Postgres and DuckDB have no rowid, there it's the row_id the row would have had with --row-id
+pub const REJECTS_TABLE: &str = "_wtd_rejects";
+
+/// A row holding values that don't fit the type of their column
+#[derive(Debug, PartialEq)]
+pub struct Reject {
+    pub rowid: i64,
+    /// Every value that didn't fit, as in Rank: 'Total' is not INTEGER
+    pub reason: String,
+    /// The cleaned values of the row as they would have been inserted
+    pub values: Vec<String>,
+}
+
+/// Whether a cleaned value can be stored in a column of the given type without sqlite
+/// quietly keeping it as something else
+fn conforms(value: &str, column_type: &SqlTypes) -> bool {
+    match column_type {
+        _ if value == "NULL" => true,
+        SqlTypes::TEXT => true,
+        SqlTypes::INTEGER => value.parse::<i64>().is_ok(),
+        SqlTypes::REAL | SqlTypes::NUMERIC => is_plain_number(value),
+    }
+}
+
+#[test]
+fn test_conforms() {
+    assert!(conforms("12", &SqlTypes::INTEGER));
+    assert!(conforms("NULL", &SqlTypes::INTEGER));
+    assert!(!conforms("12.5", &SqlTypes::INTEGER));
+    assert!(!conforms("'12'", &SqlTypes::INTEGER));
+    assert!(conforms("-12.5", &SqlTypes::REAL));
+    assert!(!conforms("'Total'", &SqlTypes::REAL));
+    assert!(conforms("12", &SqlTypes::TEXT));
+}
+
+/// Splits the rows into the ones that fit the types of the columns, paired with the rowid they
+/// get, and the ones that don't. Rowids are given out as if every row was inserted
+pub fn reject_rows(
+    columns: &[(String, String)],
+    rows: Vec<Vec<String>>,
+) -> (Vec<(i64, Vec<String>)>, Vec<Reject>) {
+    let column_types: Vec<Option<SqlTypes>> = columns
+        .iter()
+        .map(|(_, column_type)| column_type.parse().ok())
+        .collect();
+    let mut accepted = Vec::new();
+    let mut rejects = Vec::new();
+    for (rowid, row) in number_rows(rows) {
+        let problems: Vec<String> = row
+            .iter()
+            .zip(columns.iter().zip(&column_types))
+            .filter_map(|(value, ((name, _), column_type))| match column_type {
+                Some(column_type) if !conforms(value, column_type) => {
+                    Some(format!("{}: {} is not {}", name, value, column_type))
+                }
+                _ => None,
+            })
+            .collect();
+        if problems.is_empty() {
+            accepted.push((rowid, row));
+        } else {
+            rejects.push(Reject {
+                rowid,
+                reason: problems.join("; "),
+                values: row,
+            });
+        }
+    }
+    (accepted, rejects)
+}
+
+#[test]
+fn test_reject_rows() {
+    let columns = vec![
+        (String::from("Rank"), String::from("INTEGER")),
+        (String::from("Country"), String::from("TEXT")),
+    ];
+    let row =
+        |values: &[&str]| -> Vec<String> { values.iter().map(|v| String::from(*v)).collect() };
+    let rows = vec![
+        row(&["1", "'China'"]),
+        vec![],
+        row(&["'Total'", "'World'"]),
+        row(&["NULL", "'India'"]),
+    ];
+    let (accepted, rejects) = reject_rows(&columns, rows);
+    assert_eq!(
+        accepted,
+        vec![(1, row(&["1", "'China'"])), (3, row(&["NULL", "'India'"]))]
+    );
+    assert_eq!(
+        rejects,
+        vec![Reject {
+            rowid: 2,
+            reason: String::from("Rank: 'Total' is not INTEGER"),
+            values: row(&["'Total'", "'World'"]),
+        }]
+    );
+}
+
+/// Splits off the rows that don't fit the types of the columns, returning the rest with their
+/// rowids and the statement storing the rejected ones
+pub fn split_rejects(
+    table_identifier: &str,
+    columns: &[(String, String)],
+    rows: Vec<Vec<String>>,
+) -> (Vec<(i64, Vec<String>)>, String) {
+    let (accepted, rejects) = reject_rows(columns, rows);
+    println!(
+        "Rejected {} of {} rows of {}, see {}",
+        rejects.len(),
+        accepted.len() + rejects.len(),
+        table_identifier,
+        REJECTS_TABLE
+    );
+    (
+        accepted,
+        create_rejects_statement(table_identifier, &rejects),
+    )
+}
+
+/// Creates the insert statement for rows that keep their rowid, leaving gaps where rows were rejected
+pub fn create_insert_statement(
+    table_identifier: &str,
+    column_identifiers: &[String],
+    rows: &[(i64, Vec<String>)],
+) -> String {
+    if rows.is_empty() {
+        return String::new();
+    }
+    let columns: Vec<String> = column_identifiers
+        .iter()
+        .map(|column| quote_identifier(column))
+        .collect();
+    let values: Vec<String> = rows
+        .iter()
+        .map(|(rowid, row)| format!("({}, {})", rowid, row.join(", ")))
+        .collect();
+    format!(
+        "INSERT into {} (rowid, {}) VALUES {};",
+        quote_identifier(table_identifier),
+        columns.join(", "),
+        values.join(", ")
+    )
+}
+
+#[test]
+fn test_create_insert_statement() {
+    let columns = vec![String::from("Rank"), String::from("Country")];
+    let rows = vec![(1, vec![String::from("1"), String::from("'China'")])];
+    assert_eq!(
+        create_insert_statement("Population", &columns, &rows),
+        "INSERT into \"Population\" (rowid, \"Rank\", \"Country\") VALUES (1, 1, 'China');"
+    );
+    assert_eq!(create_insert_statement("Population", &columns, &[]), "");
+}
+
+/// Creates the statements that store the rejected rows of a table, creating the rejects table if needed
+/// and removing the rejects of an earlier table of the same name
+pub fn create_rejects_statement(table_identifier: &str, rejects: &[Reject]) -> String {
+    let create_statement = format!(
+        "CREATE TABLE IF NOT EXISTS {0} (table_name TEXT, row_rowid INTEGER, reason TEXT, row TEXT); \
+         DELETE FROM {0} WHERE table_name = {1};",
+        REJECTS_TABLE,
+        quote(table_identifier)
+    );
+    if rejects.is_empty() {
+        return create_statement;
+    }
+    let values: Vec<String> = rejects
+        .iter()
+        .map(|reject| {
+            format!(
+                "({}, {}, {}, {})",
+                quote(table_identifier),
+                reject.rowid,
+                quote(&reject.reason),
+                quote(&reject.values.join(", "))
+            )
+        })
+        .collect();
+    format!(
+        "{} INSERT INTO {} VALUES {};",
+        create_statement,
+        REJECTS_TABLE,
+        values.join(", ")
+    )
+}
+
+#[test]
+fn test_create_rejects_statement() {
+    let rejects = vec![Reject {
+        rowid: 2,
+        reason: String::from("Rank: 'Total' is not INTEGER"),
+        values: vec![String::from("'Total'"), String::from("'World'")],
+    }];
+    assert_eq!(
+        create_rejects_statement("Population", &rejects),
+        "CREATE TABLE IF NOT EXISTS _wtd_rejects (table_name TEXT, row_rowid INTEGER, reason TEXT, row TEXT); \
+         DELETE FROM _wtd_rejects WHERE table_name = 'Population'; \
+         INSERT INTO _wtd_rejects VALUES ('Population', 2, 'Rank: ''Total'' is not INTEGER', '''Total'', ''World''');"
+    );
+}
@@ -0,0 +1,182 @@
+use crate::numeric::{parse_number_or_hyphen_range, scale};
+
+/// A unit found in a header or after the numbers of a column
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    /// The SI unit the values are converted to with --normalize-units, empty for plain counts
+    pub si_symbol: &'static str,
+    pub factor: f64,
+}
+
+const fn unit(symbol: &'static str, si_symbol: &'static str, factor: f64) -> Unit {
+    Unit {
+        symbol,
+        si_symbol,
+        factor,
+    }
+}
+
+/// Units the way they are written on wikipedia. Temperatures are left out as they can't be
+/// converted by multiplying
+#[rustfmt::skip]
+const UNITS: &[Unit] = &[
+    unit("km²", "m²", 1e6), unit("km2", "m²", 1e6), unit("sq mi", "m²", 2_589_988.110_336),
+    unit("mi²", "m²", 2_589_988.110_336), unit("ha", "m²", 1e4), unit("m²", "m²", 1.0),
+    unit("km", "m", 1e3), unit("m", "m", 1.0), unit("cm", "m", 1e-2), unit("mm", "m", 1e-3),
+    unit("mi", "m", 1_609.344), unit("ft", "m", 0.3048),
+    unit("km³", "m³", 1e9), unit("m³", "m³", 1.0), unit("L", "m³", 1e-3),
+    unit("t", "kg", 1e3), unit("kg", "kg", 1.0), unit("g", "kg", 1e-3), unit("lb", "kg", 0.453_592_37),
+    unit("km/h", "m/s", 1.0 / 3.6), unit("mph", "m/s", 0.447_04), unit("m/s", "m/s", 1.0),
+    unit("h", "s", 3_600.0), unit("min", "s", 60.0), unit("s", "s", 1.0),
+    unit("GW", "W", 1e9), unit("MW", "W", 1e6), unit("kW", "W", 1e3), unit("W", "W", 1.0),
+    unit("thousands", "", 1e3), unit("millions", "", 1e6), unit("billions", "", 1e9),
+];
+
+/// Significant digits kept when converting with a factor that isn't a power of ten, so that
+/// 3 ft is 0.9144 m rather than 0.9144000000000001
+const SIGNIFICANT_DIGITS: usize = 12;
+
+impl Unit {
+    /// Converts a number in this unit to the SI unit. Powers of ten move the decimal point so the
+    /// result is exact, other factors are multiplied and rounded
+    pub fn normalize(&self, number: &str) -> String {
+        let exponent = self.factor.log10().round() as i32;
+        match number.parse::<f64>() {
+            Ok(_) if 10f64.powi(exponent) == self.factor => scale(number, exponent),
+            Ok(value) => format!("{:.*e}", SIGNIFICANT_DIGITS - 1, value * self.factor)
+                .parse::<f64>()
+                .map_or_else(|_| String::from(number), |rounded| rounded.to_string()),
+            Err(_) => String::from(number),
+        }
+    }
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(find_unit("km").unwrap().normalize("12.5"), "12500");
+    assert_eq!(find_unit("km").unwrap().normalize("1.005"), "1005");
+    assert_eq!(find_unit("mm").unwrap().normalize("1.5"), "0.0015");
+    assert_eq!(find_unit("millions").unwrap().normalize("3"), "3000000");
+    assert_eq!(find_unit("m").unwrap().normalize("8848"), "8848");
+    assert_eq!(find_unit("ft").unwrap().normalize("3"), "0.9144");
+    assert_eq!(find_unit("km/h").unwrap().normalize("36"), "10");
+    assert_eq!(find_unit("km").unwrap().normalize("n/a"), "n/a");
+}
+
+fn find_unit(symbol: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|unit| {
+        // Words can be capitalized, symbols can't as MW and mW are different units
+        unit.symbol == symbol || (unit.symbol.len() > 3 && unit.symbol.eq_ignore_ascii_case(symbol))
+    })
+}
+
+/// The unit in a parenthetical at the end of a header such as Area (km²) or Population (millions)
+pub fn header_unit(header: &str) -> Option<&'static Unit> {
+    let (_, parenthetical) = header.trim_end().strip_suffix(')')?.rsplit_once('(')?;
+    find_unit(parenthetical.trim())
+}
+
+#[test]
+fn test_header_unit() {
+    assert_eq!(header_unit("Area (km²)").unwrap().symbol, "km²");
+    assert_eq!(
+        header_unit("Population (Millions)").unwrap().symbol,
+        "millions"
+    );
+    assert_eq!(header_unit("Population (2020)"), None);
+    assert_eq!(header_unit("Area"), None);
+}
+
+/// Splits a number followed by a unit such as 12.5 km into the number and the unit
+pub fn split_unit_suffix(text: &str) -> Option<(&str, &'static Unit)> {
+    UNITS
+        .iter()
+        .filter_map(|unit| {
+            let before = text.strip_suffix(unit.symbol)?;
+            let number = before.trim_end();
+            // Single letters need a space so the s of 1990s isn't taken for seconds
+            let spaced = unit.symbol.chars().count() > 1 || before.len() > number.len();
+            // Otherwise the m of 12 km would be taken for metres
+            if spaced && number.ends_with(|c: char| c.is_ascii_digit()) {
+                Some((number, unit))
+            } else {
+                None
+            }
+        })
+        .max_by_key(|(_, unit)| unit.symbol.len())
+}
+
+#[test]
+fn test_split_unit_suffix() {
+    assert_eq!(split_unit_suffix("12.5 km").unwrap().0, "12.5");
+    assert_eq!(split_unit_suffix("12.5 km").unwrap().1.symbol, "km");
+    assert_eq!(split_unit_suffix("300 m").unwrap().1.symbol, "m");
+    assert_eq!(split_unit_suffix("1990s"), None);
+    assert_eq!(split_unit_suffix("10–20 km²").unwrap().0, "10–20");
+    assert_eq!(split_unit_suffix("Kingdom"), None);
+}
+
+/// Returns the unit of a column of numbers, taken from its header or, failing that, from a unit
+/// that every number in the column ends with. Numbers without a unit are taken to be in the
+/// column's unit. A column holding anything but numbers has no unit, whatever its header says
+pub fn column_unit<'a>(
+    header: &str,
+    cells: impl Iterator<Item = &'a String>,
+    decimal_comma: bool,
+) -> Option<&'static Unit> {
+    let is_number = |text: &str| parse_number_or_hyphen_range(text, decimal_comma).is_some();
+    let mut cells = cells.filter(|cell| !cell.is_empty());
+    if let Some(unit) = header_unit(header) {
+        return if cells.all(|cell| is_number(&strip_unit(cell, unit))) {
+            Some(unit)
+        } else {
+            None
+        };
+    }
+    let mut column_unit: Option<&'static Unit> = None;
+    for cell in cells {
+        match split_unit_suffix(cell) {
+            Some((number, unit)) if is_number(number) => match column_unit {
+                Some(column_unit) if column_unit.symbol != unit.symbol => return None,
+                _ => column_unit = Some(unit),
+            },
+            _ if is_number(cell) => {}
+            _ => return None,
+        }
+    }
+    column_unit
+}
+
+#[test]
+fn test_column_unit() {
+    let cells =
+        |cells: &[&str]| -> Vec<String> { cells.iter().map(|c| String::from(*c)).collect() };
+    let lengths = cells(&["12.5 km", "8 km", "3"]);
+    assert_eq!(
+        column_unit("Length", lengths.iter(), false).unwrap().symbol,
+        "km"
+    );
+    let mixed = cells(&["12.5 km", "800 m"]);
+    assert_eq!(column_unit("Length", mixed.iter(), false), None);
+    let names = cells(&["12.5 km", "Kingdom"]);
+    assert_eq!(column_unit("Length", names.iter(), false), None);
+    assert_eq!(column_unit("Area (km²)", names.iter(), false), None);
+    let sizes = cells(&["big"]);
+    assert_eq!(column_unit("Size (m)", sizes.iter(), false), None);
+    let areas = cells(&["12", "3 km²", "10-20"]);
+    assert_eq!(
+        column_unit("Area (km²)", areas.iter(), false)
+            .unwrap()
+            .symbol,
+        "km²"
+    );
+}
+
+/// Removes the unit from the end of a cell
+pub fn strip_unit(cell: &str, unit: &Unit) -> String {
+    match split_unit_suffix(cell) {
+        Some((number, found)) if found.symbol == unit.symbol => String::from(number),
+        _ => String::from(cell),
+    }
+}